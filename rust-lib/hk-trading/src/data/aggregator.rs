@@ -0,0 +1,322 @@
+use chrono::{DateTime, Utc};
+
+use crate::errors::TaError;
+
+use super::{Candle, Resolution};
+
+// Which side of the book a taker trade lifted.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TakerSide {
+    Buy,
+    Sell,
+}
+
+// A single executed trade coming off a raw exchange feed. Implement this
+// over whatever trade representation a connector already produces so it
+// can be fed straight into an Aggregator without an intermediate copy.
+pub trait TakerTrade {
+    fn timestamp(&self) -> DateTime<Utc>;
+    fn price(&self) -> f64;
+    fn size(&self) -> f64;
+    fn side(&self) -> TakerSide;
+}
+
+// Consumes trades in time order and emits a finished candle whenever a
+// bucket boundary is crossed. A finished candle returned from `push_trade`
+// should be fed straight into `Candles::push_candle` by the caller.
+pub trait Aggregator {
+    // Feed one trade into the current bucket. Trades must arrive in
+    // non-decreasing timestamp order.
+    fn push_trade(&mut self, trade: &dyn TakerTrade) -> Result<Option<Candle>, TaError>;
+
+    // The bucket currently being built, if any, as a provisional
+    // in-progress candle.
+    fn current_candle(&self) -> Option<Candle>;
+}
+
+fn open_candle(open_time: DateTime<Utc>, trade: &dyn TakerTrade) -> Candle {
+    Candle {
+        open_time,
+        open: trade.price(),
+        high: trade.price(),
+        low: trade.price(),
+        close: trade.price(),
+        volume: Some(trade.size()),
+        trade_count: Some(1.0),
+    }
+}
+
+fn update_candle(candle: &mut Candle, trade: &dyn TakerTrade) {
+    candle.high = candle.high.max(trade.price());
+    candle.low = candle.low.min(trade.price());
+    candle.close = trade.price();
+    candle.volume = Some(candle.volume.unwrap_or(0.0) + trade.size());
+    candle.trade_count = Some(candle.trade_count.unwrap_or(0.0) + 1.0);
+}
+
+// Aggregates trades into fixed-length time buckets, e.g. one candle per
+// minute for Resolution::M1.
+pub struct TimeAggregator {
+    resolution: Resolution,
+    bucket_index: Option<i64>,
+    current: Option<Candle>,
+}
+
+impl TimeAggregator {
+    pub fn new(resolution: Resolution) -> Self {
+        TimeAggregator {
+            resolution,
+            bucket_index: None,
+            current: None,
+        }
+    }
+
+    fn bucket_index_for(&self, timestamp: DateTime<Utc>) -> i64 {
+        timestamp.timestamp().div_euclid(self.resolution.to_seconds())
+    }
+
+    fn bucket_open_time(&self, bucket_index: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(bucket_index * self.resolution.to_seconds(), 0)
+            .unwrap_or_else(Utc::now)
+    }
+}
+
+impl Aggregator for TimeAggregator {
+    fn push_trade(&mut self, trade: &dyn TakerTrade) -> Result<Option<Candle>, TaError> {
+        let bucket_index = self.bucket_index_for(trade.timestamp());
+
+        match self.bucket_index {
+            None => {
+                self.bucket_index = Some(bucket_index);
+                self.current = Some(open_candle(self.bucket_open_time(bucket_index), trade));
+                Ok(None)
+            }
+            Some(current_index) if bucket_index == current_index => {
+                if let Some(candle) = self.current.as_mut() {
+                    update_candle(candle, trade);
+                }
+                Ok(None)
+            }
+            Some(current_index) if bucket_index > current_index => {
+                let finished = self.current.take();
+                self.bucket_index = Some(bucket_index);
+                self.current = Some(open_candle(self.bucket_open_time(bucket_index), trade));
+                Ok(finished)
+            }
+            Some(_) => Err(TaError::InvalidParameter),
+        }
+    }
+
+    fn current_candle(&self) -> Option<Candle> {
+        self.current
+    }
+}
+
+// Aggregates trades into a candle every `threshold` trades (a "tick bar").
+pub struct CountAggregator {
+    threshold: usize,
+    trade_count: usize,
+    current: Option<Candle>,
+}
+
+impl CountAggregator {
+    pub fn new(threshold: usize) -> Self {
+        CountAggregator {
+            threshold,
+            trade_count: 0,
+            current: None,
+        }
+    }
+}
+
+impl Aggregator for CountAggregator {
+    fn push_trade(&mut self, trade: &dyn TakerTrade) -> Result<Option<Candle>, TaError> {
+        if self.threshold == 0 {
+            return Err(TaError::InvalidParameter);
+        }
+
+        match self.current.as_mut() {
+            None => self.current = Some(open_candle(trade.timestamp(), trade)),
+            Some(candle) => update_candle(candle, trade),
+        }
+        self.trade_count += 1;
+
+        if self.trade_count >= self.threshold {
+            self.trade_count = 0;
+            Ok(self.current.take())
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn current_candle(&self) -> Option<Candle> {
+        self.current
+    }
+}
+
+// Aggregates trades into a candle once accumulated volume reaches
+// `threshold` (a "volume bar").
+pub struct VolumeAggregator {
+    threshold: f64,
+    volume: f64,
+    current: Option<Candle>,
+}
+
+impl VolumeAggregator {
+    pub fn new(threshold: f64) -> Self {
+        VolumeAggregator {
+            threshold,
+            volume: 0.0,
+            current: None,
+        }
+    }
+}
+
+impl Aggregator for VolumeAggregator {
+    fn push_trade(&mut self, trade: &dyn TakerTrade) -> Result<Option<Candle>, TaError> {
+        if self.threshold <= 0.0 {
+            return Err(TaError::InvalidParameter);
+        }
+
+        match self.current.as_mut() {
+            None => self.current = Some(open_candle(trade.timestamp(), trade)),
+            Some(candle) => update_candle(candle, trade),
+        }
+        self.volume += trade.size();
+
+        if self.volume >= self.threshold {
+            self.volume = 0.0;
+            Ok(self.current.take())
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn current_candle(&self) -> Option<Candle> {
+        self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixtureTrade {
+        timestamp: DateTime<Utc>,
+        price: f64,
+        size: f64,
+    }
+
+    impl TakerTrade for FixtureTrade {
+        fn timestamp(&self) -> DateTime<Utc> {
+            self.timestamp
+        }
+
+        fn price(&self) -> f64 {
+            self.price
+        }
+
+        fn size(&self) -> f64 {
+            self.size
+        }
+
+        fn side(&self) -> TakerSide {
+            TakerSide::Buy
+        }
+    }
+
+    fn trade_at(seconds: i64, price: f64, size: f64) -> FixtureTrade {
+        FixtureTrade {
+            timestamp: DateTime::from_timestamp(seconds, 0).unwrap(),
+            price,
+            size,
+        }
+    }
+
+    #[test]
+    fn count_aggregator_emits_a_candle_every_n_trades() {
+        let mut aggregator = CountAggregator::new(2);
+        let now = Utc::now();
+
+        let first = aggregator
+            .push_trade(&FixtureTrade {
+                timestamp: now,
+                price: 1.0,
+                size: 1.0,
+            })
+            .unwrap();
+        assert!(first.is_none());
+
+        let second = aggregator
+            .push_trade(&FixtureTrade {
+                timestamp: now,
+                price: 2.0,
+                size: 1.0,
+            })
+            .unwrap()
+            .expect("candle closes on the second trade");
+        assert_eq!(second.open, 1.0);
+        assert_eq!(second.close, 2.0);
+        assert_eq!(second.high, 2.0);
+        assert_eq!(second.volume, Some(2.0));
+        assert_eq!(second.trade_count, Some(2.0));
+        assert!(aggregator.current_candle().is_none());
+    }
+
+    #[test]
+    fn time_aggregator_accumulates_ohlcv_within_a_bucket_and_closes_on_boundary_cross() {
+        let mut aggregator = TimeAggregator::new(Resolution::M1);
+
+        assert!(aggregator.push_trade(&trade_at(0, 10.0, 1.0)).unwrap().is_none());
+        assert!(aggregator.push_trade(&trade_at(30, 12.0, 2.0)).unwrap().is_none());
+        assert!(aggregator.push_trade(&trade_at(45, 8.0, 1.0)).unwrap().is_none());
+
+        let in_progress = aggregator
+            .current_candle()
+            .expect("the open bucket is queryable before it closes");
+        assert_eq!(in_progress.open, 10.0);
+        assert_eq!(in_progress.high, 12.0);
+        assert_eq!(in_progress.low, 8.0);
+        assert_eq!(in_progress.close, 8.0);
+        assert_eq!(in_progress.volume, Some(4.0));
+        assert_eq!(in_progress.trade_count, Some(3.0));
+
+        let finished = aggregator
+            .push_trade(&trade_at(61, 9.0, 3.0))
+            .unwrap()
+            .expect("the trade past the minute boundary closes the bucket");
+        assert_eq!(finished.open_time, DateTime::from_timestamp(0, 0).unwrap());
+        assert_eq!(finished.open, 10.0);
+        assert_eq!(finished.high, 12.0);
+        assert_eq!(finished.low, 8.0);
+        assert_eq!(finished.close, 8.0);
+        assert_eq!(finished.volume, Some(4.0));
+        assert_eq!(finished.trade_count, Some(3.0));
+
+        let next_bucket = aggregator
+            .current_candle()
+            .expect("the trade that closed the old bucket opens the next one");
+        assert_eq!(next_bucket.open, 9.0);
+        assert_eq!(next_bucket.trade_count, Some(1.0));
+    }
+
+    #[test]
+    fn volume_aggregator_emits_a_candle_once_accumulated_volume_crosses_threshold() {
+        let mut aggregator = VolumeAggregator::new(5.0);
+
+        assert!(aggregator.push_trade(&trade_at(0, 1.0, 2.0)).unwrap().is_none());
+        assert_eq!(aggregator.current_candle().unwrap().volume, Some(2.0));
+
+        assert!(aggregator.push_trade(&trade_at(1, 2.0, 2.0)).unwrap().is_none());
+
+        let finished = aggregator
+            .push_trade(&trade_at(2, 3.0, 1.0))
+            .unwrap()
+            .expect("candle closes once volume reaches the threshold");
+        assert_eq!(finished.open, 1.0);
+        assert_eq!(finished.close, 3.0);
+        assert_eq!(finished.volume, Some(5.0));
+        assert_eq!(finished.trade_count, Some(3.0));
+        assert!(aggregator.current_candle().is_none());
+    }
+}
@@ -1,10 +1,33 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::errors::HkError;
+use crate::errors::{HkError, TaError};
 
+use super::udf::{UdfResponse, UdfStatus};
 use super::Resolution;
 
+// Sum the `Some` values in `values`, skipping `None`s; `None` only if every
+// value was `None`.
+fn sum_optional(values: impl Iterator<Item = Option<f64>>) -> Option<f64> {
+    let mut total = 0.0;
+    let mut seen = false;
+    for value in values.flatten() {
+        total += value;
+        seen = true;
+    }
+    if seen {
+        Some(total)
+    } else {
+        None
+    }
+}
+
+// Escape commas, spaces and equals signs per the InfluxDB line protocol
+// rules for measurement names, tag keys and tag values.
+fn escape_line_protocol_key(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(',', "\\,").replace(' ', "\\ ").replace('=', "\\=")
+}
+
 #[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Deserialize, Serialize)]
 pub struct Candle {
     pub open_time: DateTime<Utc>,
@@ -22,6 +45,15 @@ pub struct TimestampValue {
     pub value: f64,
 }
 
+// How to synthesize candles for the missing buckets a gap spans.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FillStrategy {
+    // Repeat the previous close as a flat OHLC bar with zero volume.
+    ForwardFill,
+    // Linearly interpolate OHLC between the surrounding closes.
+    Interpolate,
+}
+
 #[derive(Clone, PartialEq, PartialOrd, Debug, Default)]
 pub struct Candles {
     // DESC order
@@ -82,6 +114,11 @@ impl Candles {
         self
     }
 
+    pub fn set_trade_count(&mut self, trade_count: Vec<Option<f64>>) -> &mut Self {
+        self.trade_count = trade_count;
+        self
+    }
+
     pub fn time_desc(&self) -> Option<bool> {
         self.time_desc
     }
@@ -90,6 +127,23 @@ impl Candles {
         self.resolution
     }
 
+    // Set the resolution directly, e.g. from an authoritative tag restored
+    // by a persistence-layer reader rather than detected from the rows.
+    pub fn set_resolution(&mut self, resolution: Resolution) -> &mut Self {
+        self.resolution = Some(resolution);
+        self
+    }
+
+    // Detect `time_desc` and `resolution` from the current rows. Callers
+    // that populate a `Candles` directly through the `set_*` builders (bulk
+    // loaders such as the CSV reader) should call this once afterwards,
+    // since those setters bypass the detection `push_candle` does per-row.
+    pub fn detect_metadata(&mut self) -> &mut Self {
+        self.detect_resolution();
+        self.detect_time_desc();
+        self
+    }
+
     fn detect_time_desc(&mut self) {
         if self.time_desc == None {
             self.time_desc = if self.open_times.len() > 1 {
@@ -104,7 +158,9 @@ impl Candles {
     fn detect_resolution(&mut self) {
         if self.resolution == None {
             self.resolution = if self.open_times.len() >= 2 {
-                Resolution::from_seconds((self.open_times[0] - self.open_times[1]).num_seconds())
+                // Bucket width doesn't depend on direction, so use the gap's
+                // magnitude: `open_times` may be ASC or DESC at this point.
+                Resolution::from_seconds((self.open_times[0] - self.open_times[1]).num_seconds().abs())
             } else {
                 None
             }
@@ -156,6 +212,150 @@ impl Candles {
         Ok(self)
     }
 
+    // Relaxed ingestion path for feeds that may skip bars: only checks that
+    // the new candle continues in the series' existing time direction,
+    // without requiring it to be exactly one resolution step away. Use
+    // `detect_gaps`/`fill_gaps` afterwards to patch the resulting holes.
+    pub fn push_candle_allow_gaps(&mut self, candle: &Candle) -> Result<&mut Self, TaError> {
+        match self.time_desc() {
+            Some(true) => {
+                if let Some(&last_open_time) = self.open_times.last() {
+                    if candle.open_time >= last_open_time {
+                        return Err(TaError::InvalidParameter);
+                    }
+                }
+            }
+            Some(false) => {
+                if let Some(&last_open_time) = self.open_times.last() {
+                    if candle.open_time <= last_open_time {
+                        return Err(TaError::InvalidParameter);
+                    }
+                }
+            }
+            None => {}
+        }
+
+        self.open_times.push(candle.open_time);
+        self.opens.push(candle.open);
+        self.highs.push(candle.high);
+        self.lows.push(candle.low);
+        self.closes.push(candle.close);
+        self.volumes.push(candle.volume);
+        self.trade_count.push(candle.trade_count);
+
+        self.detect_resolution();
+        self.detect_time_desc();
+
+        Ok(self)
+    }
+
+    fn to_ascending_candles(&self) -> Vec<Candle> {
+        let mut rows: Vec<Candle> = (0..self.open_times.len())
+            .map(|i| Candle {
+                open_time: self.open_times[i],
+                open: self.opens[i],
+                high: self.highs[i],
+                low: self.lows[i],
+                close: self.closes[i],
+                volume: self.volumes[i],
+                trade_count: self.trade_count[i],
+            })
+            .collect();
+        if self.time_desc().unwrap_or(true) {
+            rows.reverse();
+        }
+        rows
+    }
+
+    fn replace_rows(&mut self, mut rows: Vec<Candle>) {
+        if self.time_desc().unwrap_or(true) {
+            rows.reverse();
+        }
+        self.open_times = rows.iter().map(|c| c.open_time).collect();
+        self.opens = rows.iter().map(|c| c.open).collect();
+        self.highs = rows.iter().map(|c| c.high).collect();
+        self.lows = rows.iter().map(|c| c.low).collect();
+        self.closes = rows.iter().map(|c| c.close).collect();
+        self.volumes = rows.iter().map(|c| c.volume).collect();
+        self.trade_count = rows.iter().map(|c| c.trade_count).collect();
+    }
+
+    // Find every adjacent pair of candles whose time delta is a whole
+    // multiple greater than one of the resolution step, i.e. a missing
+    // interval of bars, returned as the open times of the two candles that
+    // bracket it. Off-grid deltas are not reported.
+    pub fn detect_gaps(&self) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let resolution_seconds = match self.resolution() {
+            Some(resolution) => resolution.to_seconds(),
+            None => return vec![],
+        };
+
+        self.to_ascending_candles()
+            .windows(2)
+            .filter_map(|pair| {
+                let delta = (pair[1].open_time - pair[0].open_time).num_seconds();
+                if delta > resolution_seconds && delta % resolution_seconds == 0 {
+                    Some((pair[0].open_time, pair[1].open_time))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    // Synthesize candles for every missing bucket `detect_gaps` would
+    // report, so the series becomes contiguous at its own resolution.
+    pub fn fill_gaps(&mut self, strategy: FillStrategy) {
+        let resolution_seconds = match self.resolution() {
+            Some(resolution) => resolution.to_seconds(),
+            None => return,
+        };
+
+        let ascending = self.to_ascending_candles();
+        if ascending.is_empty() {
+            return;
+        }
+
+        let mut filled = Vec::with_capacity(ascending.len());
+        filled.push(ascending[0]);
+        for pair in ascending.windows(2) {
+            let (previous, next) = (pair[0], pair[1]);
+            let delta = (next.open_time - previous.open_time).num_seconds();
+            // Only synthesize bars for gaps `detect_gaps` would actually report:
+            // an off-grid delta is left alone rather than filled onto a grid
+            // `next` doesn't itself land on.
+            let missing_steps = if delta > resolution_seconds && delta % resolution_seconds == 0 {
+                delta / resolution_seconds - 1
+            } else {
+                0
+            };
+
+            for step in 1..=missing_steps {
+                let open_time = previous.open_time + Duration::seconds(resolution_seconds * step);
+                let close = match strategy {
+                    FillStrategy::ForwardFill => previous.close,
+                    FillStrategy::Interpolate => {
+                        let ratio = step as f64 / (missing_steps + 1) as f64;
+                        previous.close + (next.close - previous.close) * ratio
+                    }
+                };
+                filled.push(Candle {
+                    open_time,
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: Some(0.0),
+                    trade_count: Some(0.0),
+                });
+            }
+
+            filled.push(next);
+        }
+
+        self.replace_rows(filled);
+    }
+
     #[inline]
     pub fn get_last_close_time(&self) -> Option<DateTime<Utc>> {
         if self.open_times.len() > 0 {
@@ -173,10 +373,187 @@ impl Candles {
             None
         }
     }
+
+    // Aggregate this series into coarser `target` buckets, e.g. M1 -> M15.
+    // `target` must be an integer multiple of the source resolution.
+    pub fn resample(&self, target: Resolution) -> Result<Candles, TaError> {
+        let source_resolution = self.resolution().ok_or(TaError::InvalidParameter)?;
+        let source_seconds = source_resolution.to_seconds();
+        let target_seconds = target.to_seconds();
+        if target_seconds < source_seconds || target_seconds % source_seconds != 0 {
+            return Err(TaError::InvalidParameter);
+        }
+
+        // Walk the source rows oldest-first so each group is built as a
+        // contiguous forward run, regardless of the source's own ordering.
+        let source_desc = self.time_desc().unwrap_or(true);
+        let mut indices: Vec<usize> = (0..self.open_times.len()).collect();
+        if source_desc {
+            indices.reverse();
+        }
+
+        let mut groups: Vec<Vec<usize>> = vec![];
+        let mut current_bucket: Option<i64> = None;
+        for index in indices {
+            let bucket = self.open_times[index].timestamp().div_euclid(target_seconds);
+            if current_bucket != Some(bucket) {
+                groups.push(vec![]);
+                current_bucket = Some(bucket);
+            }
+            groups.last_mut().unwrap().push(index);
+        }
+
+        let mut resampled = Candles::new();
+        for group in groups.iter() {
+            resampled.push_resampled_group(self, group, target_seconds)?;
+        }
+
+        if source_desc {
+            resampled.open_times.reverse();
+            resampled.opens.reverse();
+            resampled.highs.reverse();
+            resampled.lows.reverse();
+            resampled.closes.reverse();
+            resampled.volumes.reverse();
+            resampled.trade_count.reverse();
+        }
+
+        resampled.time_desc = Some(source_desc);
+        resampled.resolution = Some(target);
+        Ok(resampled)
+    }
+
+    fn push_resampled_group(
+        &mut self,
+        source: &Candles,
+        group: &[usize],
+        target_seconds: i64,
+    ) -> Result<(), TaError> {
+        let first = *group.first().ok_or(TaError::InvalidParameter)?;
+        let last = *group.last().ok_or(TaError::InvalidParameter)?;
+
+        let bucket = source.open_times[first].timestamp().div_euclid(target_seconds);
+        let open_time = DateTime::from_timestamp(bucket * target_seconds, 0)
+            .ok_or(TaError::InvalidParameter)?;
+
+        let high = group
+            .iter()
+            .map(|&i| source.highs[i])
+            .fold(f64::NEG_INFINITY, f64::max);
+        let low = group
+            .iter()
+            .map(|&i| source.lows[i])
+            .fold(f64::INFINITY, f64::min);
+        let volume = sum_optional(group.iter().map(|&i| source.volumes[i]));
+        let trade_count = sum_optional(group.iter().map(|&i| source.trade_count[i]));
+
+        self.open_times.push(open_time);
+        self.opens.push(source.opens[first]);
+        self.highs.push(high);
+        self.lows.push(low);
+        self.closes.push(source.closes[last]);
+        self.volumes.push(volume);
+        self.trade_count.push(trade_count);
+
+        Ok(())
+    }
+
+    // Export this series as a TradingView UDF `/history` response, reordering
+    // into ASC (oldest-first) as TradingView requires if the series is DESC.
+    pub fn to_udf_response(&self, next_time: Option<DateTime<Utc>>) -> UdfResponse {
+        if self.open_times.is_empty() {
+            return UdfResponse {
+                s: UdfStatus::NoData,
+                errmsg: None,
+                next_time: next_time.map(|t| t.timestamp()),
+                t: vec![],
+                o: vec![],
+                h: vec![],
+                l: vec![],
+                c: vec![],
+                v: vec![],
+            };
+        }
+
+        let desc = self.time_desc().unwrap_or(true);
+        let reorder = |mut values: Vec<f64>| -> Vec<f64> {
+            if desc {
+                values.reverse();
+            }
+            values
+        };
+
+        let mut t: Vec<i64> = self.open_times.iter().map(|ts| ts.timestamp()).collect();
+        if desc {
+            t.reverse();
+        }
+        let o = reorder(self.opens.clone());
+        let h = reorder(self.highs.clone());
+        let l = reorder(self.lows.clone());
+        let c = reorder(self.closes.clone());
+        let v = reorder(self.volumes.iter().map(|volume| volume.unwrap_or(0.0)).collect());
+
+        UdfResponse {
+            s: UdfStatus::Ok,
+            errmsg: None,
+            next_time: None,
+            t,
+            o,
+            h,
+            l,
+            c,
+            v,
+        }
+    }
+
+    // Emit this series as InfluxDB line protocol, one line per row, so it
+    // can be snapshotted to a time-series backend. `None` fields are
+    // skipped rather than written as empty.
+    pub fn to_line_protocol(&self, measurement: &str, tags: &[(&str, &str)]) -> String {
+        let mut lines = Vec::with_capacity(self.open_times.len());
+
+        for i in 0..self.open_times.len() {
+            let mut line = escape_line_protocol_key(measurement);
+            for (key, value) in tags {
+                line.push(',');
+                line.push_str(&escape_line_protocol_key(key));
+                line.push('=');
+                line.push_str(&escape_line_protocol_key(value));
+            }
+            if let Some(resolution) = self.resolution() {
+                line.push_str(&format!(",resolution={}", resolution.as_str()));
+            }
+
+            let mut fields = vec![
+                format!("open={}", self.opens[i]),
+                format!("high={}", self.highs[i]),
+                format!("low={}", self.lows[i]),
+                format!("close={}", self.closes[i]),
+            ];
+            if let Some(volume) = self.volumes[i] {
+                fields.push(format!("volume={volume}"));
+            }
+            if let Some(trade_count) = self.trade_count[i] {
+                fields.push(format!("trade_count={trade_count}"));
+            }
+
+            let open_time_ns = self.open_times[i].timestamp_nanos_opt().unwrap_or(0);
+            line.push(' ');
+            line.push_str(&fields.join(","));
+            line.push(' ');
+            line.push_str(&open_time_ns.to_string());
+
+            lines.push(line);
+        }
+
+        lines.join("\n")
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use chrono::TimeZone;
+
     use crate::data::Candles;
 
     use super::*;
@@ -210,4 +587,324 @@ mod tests {
         // assert_eq!(candle_data_source.volume, volume);
         // assert_eq!(candle_data_source.trade_count, trade_count);
     }
+
+    #[test]
+    fn test_to_udf_response_reverses_to_ascending_order() {
+        let mut candles = Candles::new();
+        let newer = Candle {
+            open_time: Utc.timestamp_opt(120, 0).unwrap(),
+            open: 2.0,
+            high: 2.1,
+            low: 1.9,
+            close: 2.0,
+            volume: Some(20.0),
+            trade_count: Some(2.0),
+        };
+        let older = Candle {
+            open_time: Utc.timestamp_opt(60, 0).unwrap(),
+            open: 1.0,
+            high: 1.1,
+            low: 0.9,
+            close: 1.0,
+            volume: None,
+            trade_count: Some(1.0),
+        };
+        candles.push_candle(&newer).unwrap();
+        candles.push_candle(&older).unwrap();
+
+        let response = candles.to_udf_response(None);
+
+        assert_eq!(response.s, UdfStatus::Ok);
+        assert_eq!(response.t, vec![60, 120]);
+        assert_eq!(response.o, vec![1.0, 2.0]);
+        assert_eq!(response.v, vec![0.0, 20.0]);
+    }
+
+    #[test]
+    fn test_to_udf_response_leaves_ascending_order_as_is() {
+        // A series already stored ASC (e.g. from `CandleCsvReader::with_sort_desc(false)`)
+        // must not be reversed a second time.
+        let candles = Candles {
+            open_times: vec![Utc.timestamp_opt(60, 0).unwrap(), Utc.timestamp_opt(120, 0).unwrap()],
+            opens: vec![1.0, 2.0],
+            highs: vec![1.1, 2.1],
+            lows: vec![0.9, 1.9],
+            closes: vec![1.0, 2.0],
+            volumes: vec![None, Some(20.0)],
+            trade_count: vec![Some(1.0), Some(2.0)],
+            time_desc: Some(false),
+            resolution: Some(Resolution::M1),
+        };
+
+        let response = candles.to_udf_response(None);
+
+        assert_eq!(response.s, UdfStatus::Ok);
+        assert_eq!(response.t, vec![60, 120]);
+        assert_eq!(response.o, vec![1.0, 2.0]);
+        assert_eq!(response.v, vec![0.0, 20.0]);
+    }
+
+    #[test]
+    fn test_resample_groups_contiguous_buckets() {
+        // Eight M1 candles in DESC order, covering an M5 bucket [300,600)
+        // with three rows and an M5 bucket [0,300) with five rows.
+        let minutes: Vec<i64> = (0..8).rev().collect();
+        let candles = Candles {
+            open_times: minutes.iter().map(|m| Utc.timestamp_opt(m * 60, 0).unwrap()).collect(),
+            opens: minutes.iter().map(|&m| m as f64).collect(),
+            highs: minutes.iter().map(|&m| m as f64 + 0.5).collect(),
+            lows: minutes.iter().map(|&m| m as f64 - 0.5).collect(),
+            closes: minutes.iter().map(|&m| m as f64).collect(),
+            volumes: minutes.iter().map(|_| Some(1.0)).collect(),
+            trade_count: minutes.iter().map(|_| Some(1.0)).collect(),
+            time_desc: Some(true),
+            resolution: Some(Resolution::M1),
+        };
+
+        let resampled = candles.resample(Resolution::M5).unwrap();
+
+        assert_eq!(resampled.resolution(), Some(Resolution::M5));
+        assert_eq!(resampled.time_desc(), Some(true));
+        assert_eq!(
+            resampled.open_times,
+            vec![Utc.timestamp_opt(300, 0).unwrap(), Utc.timestamp_opt(0, 0).unwrap()]
+        );
+        assert_eq!(resampled.opens, vec![5.0, 0.0]);
+        assert_eq!(resampled.closes, vec![7.0, 4.0]);
+        assert_eq!(resampled.highs, vec![7.5, 4.5]);
+        assert_eq!(resampled.lows, vec![4.5, -0.5]);
+        assert_eq!(resampled.volumes, vec![Some(3.0), Some(5.0)]);
+    }
+
+    #[test]
+    fn test_resample_rejects_finer_target() {
+        let candles = Candles {
+            open_times: vec![Utc.timestamp_opt(300, 0).unwrap(), Utc.timestamp_opt(0, 0).unwrap()],
+            opens: vec![1.0, 1.0],
+            highs: vec![1.0, 1.0],
+            lows: vec![1.0, 1.0],
+            closes: vec![1.0, 1.0],
+            volumes: vec![None, None],
+            trade_count: vec![None, None],
+            time_desc: Some(true),
+            resolution: Some(Resolution::M5),
+        };
+
+        assert!(matches!(
+            candles.resample(Resolution::M1),
+            Err(TaError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_detect_gaps_finds_missing_interval() {
+        let candles = Candles {
+            open_times: vec![Utc.timestamp_opt(240, 0).unwrap(), Utc.timestamp_opt(0, 0).unwrap()],
+            opens: vec![4.0, 0.0],
+            highs: vec![4.0, 0.0],
+            lows: vec![4.0, 0.0],
+            closes: vec![4.0, 0.0],
+            volumes: vec![Some(1.0), Some(1.0)],
+            trade_count: vec![Some(1.0), Some(1.0)],
+            time_desc: Some(true),
+            resolution: Some(Resolution::M1),
+        };
+
+        let gaps = candles.detect_gaps();
+
+        assert_eq!(
+            gaps,
+            vec![(Utc.timestamp_opt(0, 0).unwrap(), Utc.timestamp_opt(240, 0).unwrap())]
+        );
+    }
+
+    #[test]
+    fn test_fill_gaps_forward_fill_repeats_previous_close() {
+        let mut candles = Candles {
+            open_times: vec![Utc.timestamp_opt(180, 0).unwrap(), Utc.timestamp_opt(0, 0).unwrap()],
+            opens: vec![3.0, 0.0],
+            highs: vec![3.0, 0.0],
+            lows: vec![3.0, 0.0],
+            closes: vec![3.0, 0.0],
+            volumes: vec![Some(1.0), Some(1.0)],
+            trade_count: vec![Some(1.0), Some(1.0)],
+            time_desc: Some(true),
+            resolution: Some(Resolution::M1),
+        };
+
+        candles.fill_gaps(FillStrategy::ForwardFill);
+
+        assert_eq!(
+            candles.open_times,
+            vec![
+                Utc.timestamp_opt(180, 0).unwrap(),
+                Utc.timestamp_opt(120, 0).unwrap(),
+                Utc.timestamp_opt(60, 0).unwrap(),
+                Utc.timestamp_opt(0, 0).unwrap(),
+            ]
+        );
+        assert_eq!(candles.closes, vec![3.0, 0.0, 0.0, 0.0]);
+        assert_eq!(candles.volumes, vec![Some(1.0), Some(0.0), Some(0.0), Some(1.0)]);
+        assert!(candles.detect_gaps().is_empty());
+    }
+
+    #[test]
+    fn test_fill_gaps_interpolate_between_surrounding_closes() {
+        let mut candles = Candles {
+            open_times: vec![Utc.timestamp_opt(180, 0).unwrap(), Utc.timestamp_opt(0, 0).unwrap()],
+            opens: vec![3.0, 0.0],
+            highs: vec![3.0, 0.0],
+            lows: vec![3.0, 0.0],
+            closes: vec![3.0, 0.0],
+            volumes: vec![Some(1.0), Some(1.0)],
+            trade_count: vec![Some(1.0), Some(1.0)],
+            time_desc: Some(true),
+            resolution: Some(Resolution::M1),
+        };
+
+        candles.fill_gaps(FillStrategy::Interpolate);
+
+        assert_eq!(candles.closes, vec![3.0, 2.0, 1.0, 0.0]);
+        assert!(candles.detect_gaps().is_empty());
+    }
+
+    #[test]
+    fn test_fill_gaps_leaves_non_multiple_delta_untouched() {
+        // A delta of 90s against an M1 (60s) resolution isn't a whole number
+        // of buckets, so there's no grid-aligned bar count to synthesize.
+        let mut candles = Candles {
+            open_times: vec![Utc.timestamp_opt(90, 0).unwrap(), Utc.timestamp_opt(0, 0).unwrap()],
+            opens: vec![1.0, 0.0],
+            highs: vec![1.0, 0.0],
+            lows: vec![1.0, 0.0],
+            closes: vec![1.0, 0.0],
+            volumes: vec![Some(1.0), Some(1.0)],
+            trade_count: vec![Some(1.0), Some(1.0)],
+            time_desc: Some(true),
+            resolution: Some(Resolution::M1),
+        };
+
+        candles.fill_gaps(FillStrategy::ForwardFill);
+
+        assert_eq!(
+            candles.open_times,
+            vec![Utc.timestamp_opt(90, 0).unwrap(), Utc.timestamp_opt(0, 0).unwrap()]
+        );
+        assert_eq!(candles.closes, vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_push_candle_allow_gaps_ingests_a_discontinuous_series() {
+        let mut candles = Candles::new();
+        candles
+            .push_candle_allow_gaps(&Candle {
+                open_time: Utc.timestamp_opt(180, 0).unwrap(),
+                open: 3.0,
+                high: 3.0,
+                low: 3.0,
+                close: 3.0,
+                volume: Some(1.0),
+                trade_count: Some(1.0),
+            })
+            .unwrap();
+        candles
+            .push_candle_allow_gaps(&Candle {
+                open_time: Utc.timestamp_opt(120, 0).unwrap(),
+                open: 2.0,
+                high: 2.0,
+                low: 2.0,
+                close: 2.0,
+                volume: Some(1.0),
+                trade_count: Some(1.0),
+            })
+            .unwrap();
+
+        // Jumps straight from 120s to 0s, skipping the 60s bar a strict
+        // `push_candle` would reject.
+        candles
+            .push_candle_allow_gaps(&Candle {
+                open_time: Utc.timestamp_opt(0, 0).unwrap(),
+                open: 0.0,
+                high: 0.0,
+                low: 0.0,
+                close: 0.0,
+                volume: Some(1.0),
+                trade_count: Some(1.0),
+            })
+            .unwrap();
+
+        assert_eq!(candles.resolution(), Some(Resolution::M1));
+        assert_eq!(
+            candles.detect_gaps(),
+            vec![(Utc.timestamp_opt(0, 0).unwrap(), Utc.timestamp_opt(120, 0).unwrap())]
+        );
+
+        let wrong_direction = candles.push_candle_allow_gaps(&Candle {
+            open_time: Utc.timestamp_opt(60, 0).unwrap(),
+            open: 1.0,
+            high: 1.0,
+            low: 1.0,
+            close: 1.0,
+            volume: None,
+            trade_count: None,
+        });
+        assert!(matches!(wrong_direction, Err(TaError::InvalidParameter)));
+    }
+
+    #[test]
+    fn test_to_line_protocol_skips_none_fields() {
+        let mut candles = Candles::new();
+        candles
+            .push_candle(&Candle {
+                open_time: Utc.timestamp_opt(60, 0).unwrap(),
+                open: 1.0,
+                high: 1.1,
+                low: 0.9,
+                close: 1.0,
+                volume: None,
+                trade_count: Some(3.0),
+            })
+            .unwrap();
+
+        let line = candles.to_line_protocol("candles", &[("symbol", "BTCUSDT")]);
+
+        assert_eq!(
+            line,
+            "candles,symbol=BTCUSDT open=1,high=1.1,low=0.9,close=1,trade_count=3 60000000000"
+        );
+    }
+
+    #[test]
+    fn test_to_line_protocol_escapes_commas_spaces_and_equals_in_tags() {
+        let mut candles = Candles::new();
+        candles
+            .push_candle(&Candle {
+                open_time: Utc.timestamp_opt(60, 0).unwrap(),
+                open: 1.0,
+                high: 1.1,
+                low: 0.9,
+                close: 1.0,
+                volume: None,
+                trade_count: None,
+            })
+            .unwrap();
+
+        let line = candles.to_line_protocol("candles,demo", &[("pair", "BTC USDT=spot")]);
+
+        assert_eq!(
+            line,
+            "candles\\,demo,pair=BTC\\ USDT\\=spot open=1,high=1.1,low=0.9,close=1 60000000000"
+        );
+    }
+
+    #[test]
+    fn test_to_udf_response_no_data() {
+        let candles = Candles::new();
+        let next_time = Utc.timestamp_opt(60, 0).unwrap();
+
+        let response = candles.to_udf_response(Some(next_time));
+
+        assert_eq!(response.s, UdfStatus::NoData);
+        assert_eq!(response.next_time, Some(60));
+    }
 }
@@ -0,0 +1,209 @@
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+
+use crate::errors::{Result, TaError};
+
+use super::{Candle, Candles};
+
+// How the configured timestamp column should be parsed.
+#[derive(Clone, Debug)]
+pub enum TimestampConversion {
+    UnixSeconds,
+    UnixMillis,
+    Rfc3339,
+    // A strftime pattern, parsed with chrono's `NaiveDateTime::parse_from_str`.
+    Format(String),
+}
+
+impl TimestampConversion {
+    fn parse(&self, value: &str) -> Result<DateTime<Utc>> {
+        match self {
+            TimestampConversion::UnixSeconds => {
+                let seconds: i64 = value
+                    .parse()
+                    .map_err(|_| TaError::UnknownError(format!("invalid unix seconds: {value}")))?;
+                DateTime::from_timestamp(seconds, 0)
+                    .ok_or_else(|| TaError::UnknownError(format!("invalid unix seconds: {value}")))
+            }
+            TimestampConversion::UnixMillis => {
+                let millis: i64 = value
+                    .parse()
+                    .map_err(|_| TaError::UnknownError(format!("invalid unix millis: {value}")))?;
+                DateTime::from_timestamp_millis(millis)
+                    .ok_or_else(|| TaError::UnknownError(format!("invalid unix millis: {value}")))
+            }
+            TimestampConversion::Rfc3339 => DateTime::parse_from_rfc3339(value)
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_err(|e| TaError::UnknownError(e.to_string())),
+            TimestampConversion::Format(format) => {
+                NaiveDateTime::parse_from_str(value, format)
+                    .map(|naive| Utc.from_utc_datetime(&naive))
+                    .map_err(|e| TaError::UnknownError(e.to_string()))
+            }
+        }
+    }
+}
+
+// Which CSV headers map to each `Candle` field.
+#[derive(Clone, Debug)]
+pub struct CsvColumnMapping {
+    pub open_time: String,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    pub volume: Option<String>,
+    pub trade_count: Option<String>,
+    pub timestamp: TimestampConversion,
+}
+
+// Loads a `Candles` from a CSV/TSV source using a configurable column
+// mapping, for offline backtest ingestion.
+pub struct CandleCsvReader {
+    mapping: CsvColumnMapping,
+    delimiter: u8,
+    // Sort rows into the crate's DESC order regardless of file order.
+    sort_desc: bool,
+}
+
+impl CandleCsvReader {
+    pub fn new(mapping: CsvColumnMapping) -> Self {
+        CandleCsvReader {
+            mapping,
+            delimiter: b',',
+            sort_desc: true,
+        }
+    }
+
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn with_sort_desc(mut self, sort_desc: bool) -> Self {
+        self.sort_desc = sort_desc;
+        self
+    }
+
+    pub fn read<R: std::io::Read>(&self, source: R) -> Result<Candles> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .from_reader(source);
+
+        let headers = reader.headers()?.clone();
+        let column_index = |name: &str| -> Result<usize> {
+            headers
+                .iter()
+                .position(|header| header == name)
+                .ok_or_else(|| TaError::CsvMissingColumn(name.to_owned()))
+        };
+
+        let open_time_index = column_index(&self.mapping.open_time)?;
+        let open_index = column_index(&self.mapping.open)?;
+        let high_index = column_index(&self.mapping.high)?;
+        let low_index = column_index(&self.mapping.low)?;
+        let close_index = column_index(&self.mapping.close)?;
+        let volume_index = self.mapping.volume.as_deref().map(column_index).transpose()?;
+        let trade_count_index = self
+            .mapping
+            .trade_count
+            .as_deref()
+            .map(column_index)
+            .transpose()?;
+
+        let mut rows = vec![];
+        for record in reader.records() {
+            let record = record?;
+            rows.push(Candle {
+                open_time: self.mapping.timestamp.parse(&record[open_time_index])?,
+                open: parse_f64(&record[open_index])?,
+                high: parse_f64(&record[high_index])?,
+                low: parse_f64(&record[low_index])?,
+                close: parse_f64(&record[close_index])?,
+                volume: volume_index.map(|index| parse_f64(&record[index])).transpose()?,
+                trade_count: trade_count_index
+                    .map(|index| parse_f64(&record[index]))
+                    .transpose()?,
+            });
+        }
+
+        if self.sort_desc {
+            rows.sort_by_key(|r| std::cmp::Reverse(r.open_time));
+        } else {
+            rows.sort_by_key(|r| r.open_time);
+        }
+
+        let mut candles = Candles::new();
+        candles
+            .set_open_times(rows.iter().map(|c| c.open_time).collect())
+            .set_opens(rows.iter().map(|c| c.open).collect())
+            .set_highs(rows.iter().map(|c| c.high).collect())
+            .set_lows(rows.iter().map(|c| c.low).collect())
+            .set_closes(rows.iter().map(|c| c.close).collect())
+            .set_volumes(rows.iter().map(|c| c.volume).collect())
+            .set_trade_count(rows.iter().map(|c| c.trade_count).collect())
+            .detect_metadata();
+
+        Ok(candles)
+    }
+}
+
+fn parse_f64(value: &str) -> Result<f64> {
+    value
+        .parse()
+        .map_err(|_| TaError::UnknownError(format!("invalid number: {value}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Resolution;
+
+    fn mapping() -> CsvColumnMapping {
+        CsvColumnMapping {
+            open_time: "time".to_owned(),
+            open: "open".to_owned(),
+            high: "high".to_owned(),
+            low: "low".to_owned(),
+            close: "close".to_owned(),
+            volume: Some("volume".to_owned()),
+            trade_count: None,
+            timestamp: TimestampConversion::UnixSeconds,
+        }
+    }
+
+    #[test]
+    fn test_read_sorts_into_desc_order() {
+        let csv = "time,open,high,low,close,volume\n0,1.0,1.1,0.9,1.0,10\n60,2.0,2.1,1.9,2.0,20\n";
+        let reader = CandleCsvReader::new(mapping());
+
+        let candles = reader.read(csv.as_bytes()).unwrap();
+
+        assert_eq!(candles.opens, vec![2.0, 1.0]);
+        assert_eq!(candles.volumes, vec![Some(20.0), Some(10.0)]);
+        assert_eq!(candles.time_desc(), Some(true));
+        assert_eq!(candles.resolution(), Resolution::from_seconds(60));
+    }
+
+    #[test]
+    fn test_read_with_sort_desc_false_keeps_asc_order_and_detects_resolution() {
+        let csv = "time,open,high,low,close,volume\n0,1.0,1.1,0.9,1.0,10\n60,2.0,2.1,1.9,2.0,20\n";
+        let reader = CandleCsvReader::new(mapping()).with_sort_desc(false);
+
+        let candles = reader.read(csv.as_bytes()).unwrap();
+
+        assert_eq!(candles.opens, vec![1.0, 2.0]);
+        assert_eq!(candles.volumes, vec![Some(10.0), Some(20.0)]);
+        assert_eq!(candles.time_desc(), Some(false));
+        assert_eq!(candles.resolution(), Resolution::from_seconds(60));
+    }
+
+    #[test]
+    fn test_read_missing_column_is_reported() {
+        let csv = "time,open,high,low,close\n0,1.0,1.1,0.9,1.0\n";
+        let reader = CandleCsvReader::new(mapping());
+
+        let error = reader.read(csv.as_bytes()).unwrap_err();
+
+        assert!(matches!(error, TaError::CsvMissingColumn(name) if name == "volume"));
+    }
+}
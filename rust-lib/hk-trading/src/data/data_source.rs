@@ -7,7 +7,7 @@ use crate::errors::TaError;
 
 use super::Candle;
 
-#[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Debug, Hash, Default)]
 pub enum Resolution {
     #[default]
     M1,
@@ -51,6 +51,33 @@ impl Resolution {
             _ => None,
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Resolution::M1 => "M1",
+            Resolution::M5 => "M5",
+            Resolution::M15 => "M15",
+            Resolution::M30 => "M30",
+            Resolution::H1 => "H1",
+            Resolution::H4 => "H4",
+            Resolution::D1 => "D1",
+            Resolution::W1 => "W1",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Resolution> {
+        match value {
+            "M1" => Some(Resolution::M1),
+            "M5" => Some(Resolution::M5),
+            "M15" => Some(Resolution::M15),
+            "M30" => Some(Resolution::M30),
+            "H1" => Some(Resolution::H1),
+            "H4" => Some(Resolution::H4),
+            "D1" => Some(Resolution::D1),
+            "W1" => Some(Resolution::W1),
+            _ => None,
+        }
+    }
 }
 
 pub struct DataSourceMeta {
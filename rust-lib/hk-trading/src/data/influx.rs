@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+
+use super::{Candles, Resolution};
+
+// One row as returned by an InfluxDB query against a series written with
+// `Candles::to_line_protocol`.
+#[derive(Clone, Debug)]
+pub struct InfluxCandleRow {
+    pub open_time: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: Option<f64>,
+    pub trade_count: Option<f64>,
+    pub resolution: Option<String>,
+}
+
+// Rebuilds a `Candles` from InfluxDB query rows, restoring the crate's
+// DESC ordering and the `resolution` tag, so downloaded history can be
+// hydrated without re-fetching it from an exchange.
+pub fn candles_from_influx_rows(mut rows: Vec<InfluxCandleRow>) -> Candles {
+    rows.sort_by_key(|r| std::cmp::Reverse(r.open_time));
+
+    let resolution = rows
+        .first()
+        .and_then(|row| row.resolution.as_deref())
+        .and_then(Resolution::parse);
+
+    let mut candles = Candles::new();
+    candles
+        .set_open_times(rows.iter().map(|row| row.open_time).collect())
+        .set_opens(rows.iter().map(|row| row.open).collect())
+        .set_highs(rows.iter().map(|row| row.high).collect())
+        .set_lows(rows.iter().map(|row| row.low).collect())
+        .set_closes(rows.iter().map(|row| row.close).collect())
+        .set_volumes(rows.iter().map(|row| row.volume).collect())
+        .set_trade_count(rows.iter().map(|row| row.trade_count).collect())
+        .detect_metadata();
+
+    if let Some(resolution) = resolution {
+        candles.set_resolution(resolution);
+    }
+
+    candles
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_candles_from_influx_rows_restores_desc_order_and_resolution() {
+        let rows = vec![
+            InfluxCandleRow {
+                open_time: Utc.timestamp_opt(0, 0).unwrap(),
+                open: 1.0,
+                high: 1.0,
+                low: 1.0,
+                close: 1.0,
+                volume: Some(10.0),
+                trade_count: None,
+                resolution: Some("M1".to_owned()),
+            },
+            InfluxCandleRow {
+                open_time: Utc.timestamp_opt(60, 0).unwrap(),
+                open: 2.0,
+                high: 2.0,
+                low: 2.0,
+                close: 2.0,
+                volume: Some(20.0),
+                trade_count: None,
+                resolution: Some("M1".to_owned()),
+            },
+        ];
+
+        let candles = candles_from_influx_rows(rows);
+
+        assert_eq!(
+            candles.open_times,
+            vec![Utc.timestamp_opt(60, 0).unwrap(), Utc.timestamp_opt(0, 0).unwrap()]
+        );
+        assert_eq!(candles.opens, vec![2.0, 1.0]);
+        assert_eq!(candles.resolution(), Some(Resolution::M1));
+    }
+}
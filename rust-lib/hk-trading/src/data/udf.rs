@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+// The `s` field of a TradingView UDF `/history` response.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UdfStatus {
+    Ok,
+    NoData,
+    Error,
+}
+
+// TradingView UDF `/history` response. `t`/`o`/`h`/`l`/`c`/`v` are parallel
+// arrays in ASC (oldest-first) order, with timestamps as unix seconds.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct UdfResponse {
+    pub s: UdfStatus,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errmsg: Option<String>,
+
+    #[serde(rename = "nextTime", skip_serializing_if = "Option::is_none")]
+    pub next_time: Option<i64>,
+
+    pub t: Vec<i64>,
+    pub o: Vec<f64>,
+    pub h: Vec<f64>,
+    pub l: Vec<f64>,
+    pub c: Vec<f64>,
+    pub v: Vec<f64>,
+}